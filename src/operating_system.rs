@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs,
     io::{BufRead, BufReader},
     result::Result as StdResult,
@@ -6,6 +7,23 @@ use std::{
 };
 
 use crate::error::{Error, Result};
+use crate::version::Version;
+
+/// An [`OperatingSystem`] together with the version detected for it
+#[derive(Debug, Clone, Copy)]
+pub struct DetectedOs {
+    pub os: OperatingSystem,
+    pub version: Version,
+}
+
+impl DetectedOs {
+    /// Whether the detected version is at least `min`, so callers can
+    /// refuse to proceed on an OS that's too old instead of emitting
+    /// install commands that will fail
+    pub fn satisfies_min_version(&self, min: &Version) -> bool {
+        self.version >= *min
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub enum OperatingSystem {
@@ -13,6 +31,9 @@ pub enum OperatingSystem {
     Debian,
     RedHat,
     Windows,
+    Arch,
+    Alpine,
+    Suse,
 }
 
 impl FromStr for OperatingSystem {
@@ -24,6 +45,9 @@ impl FromStr for OperatingSystem {
             "debian" => Ok(OperatingSystem::Debian),
             "redhat" | "rhel" => Ok(OperatingSystem::RedHat),
             "windows" | "win" => Ok(OperatingSystem::Windows),
+            "arch" => Ok(OperatingSystem::Arch),
+            "alpine" => Ok(OperatingSystem::Alpine),
+            "suse" | "opensuse" => Ok(OperatingSystem::Suse),
             _ => Err(format!("Invalid operating system: '{}'", s)),
         }
     }
@@ -37,6 +61,9 @@ impl OperatingSystem {
             OperatingSystem::Debian => &[PackageManager::Apt],
             OperatingSystem::RedHat => &[PackageManager::Dnf, PackageManager::Yum],
             OperatingSystem::Windows => &[PackageManager::Chocolatey],
+            OperatingSystem::Arch => &[PackageManager::Pacman],
+            OperatingSystem::Alpine => &[PackageManager::Apk],
+            OperatingSystem::Suse => &[PackageManager::Zypper],
         }
     }
 
@@ -55,24 +82,122 @@ impl OperatingSystem {
         os.ok_or(Error::UnsupportedOperatingSystem)
     }
 
+    /// Detect the current operating system along with its version, so
+    /// callers can gate on a minimum supported release
+    pub fn detect_with_version() -> Result<DetectedOs> {
+        let detected = if cfg!(target_os = "linux") {
+            Self::detect_linux_distribution_with_version()
+        } else if cfg!(windows) {
+            Self::detect_windows_version().map(|version| (OperatingSystem::Windows, version))
+        } else if cfg!(target_os = "macos") {
+            Self::detect_macos_version().map(|version| (OperatingSystem::Mac, version))
+        } else {
+            None
+        };
+
+        let (os, version) = detected.ok_or(Error::UnsupportedOperatingSystem)?;
+
+        Ok(DetectedOs { os, version })
+    }
+
     /// Check `os-release` to detect current Linux distro
     fn detect_linux_distribution() -> Option<OperatingSystem> {
+        Self::detect_linux_distribution_with_version().map(|(os, _)| os)
+    }
+
+    /// Check `os-release` to detect the current Linux distro and its `VERSION_ID`
+    fn detect_linux_distribution_with_version() -> Option<(OperatingSystem, Version)> {
         let os_release = fs::File::open("/etc/os-release").ok()?;
         let reader = BufReader::new(os_release);
 
+        let fields = Self::parse_os_release(reader);
+
+        Self::resolve_linux_os_and_version(&fields)
+    }
+
+    /// Resolve the distro family (falling back from `ID` to `ID_LIKE`) and
+    /// `VERSION_ID` from already-parsed `os-release` fields
+    fn resolve_linux_os_and_version(
+        fields: &HashMap<String, String>,
+    ) -> Option<(OperatingSystem, Version)> {
+        let os = fields
+            .get("ID")
+            .and_then(|id| Self::os_from_id(id))
+            .or_else(|| {
+                fields
+                    .get("ID_LIKE")?
+                    .split_whitespace()
+                    .find_map(Self::os_from_id)
+            })?;
+
+        let version = fields
+            .get("VERSION_ID")
+            .and_then(|version_id| version_id.parse().ok())
+            .unwrap_or_default();
+
+        Some((os, version))
+    }
+
+    /// Shell out to `sw_vers` to get the running macOS version
+    fn detect_macos_version() -> Option<Version> {
+        let output = std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()?;
+
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+
+    /// Shell out to `ver` to get the running Windows build/version
+    fn detect_windows_version() -> Option<Version> {
+        let output = std::process::Command::new("cmd")
+            .args(["/C", "ver"])
+            .output()
+            .ok()?;
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let version = text
+            .split('[')
+            .nth(1)?
+            .trim()
+            .trim_start_matches("Version ")
+            .trim_end_matches(']');
+
+        version.parse().ok()
+    }
+
+    /// Parse `os-release` key=value pairs, stripping surrounding quotes from values
+    fn parse_os_release(reader: impl BufRead) -> HashMap<String, String> {
+        let mut fields = HashMap::new();
+
         for maybe_line in reader.lines() {
             let Ok(line) = maybe_line else {
                 continue;
             };
 
-            match &*line {
-                "ID=debian" => return Some(OperatingSystem::Debian),
-                "ID=fedora" | "ID=centos" | "ID=rhel" => return Some(OperatingSystem::RedHat),
-                _ => continue,
-            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            let value = value.trim().trim_matches('\'').trim_matches('"');
+            fields.insert(key.trim().to_owned(), value.to_owned());
         }
 
-        None
+        fields
+    }
+
+    /// Map a single `ID`/`ID_LIKE` token to a supported `OperatingSystem`
+    fn os_from_id(id: &str) -> Option<OperatingSystem> {
+        match id {
+            "debian" | "ubuntu" | "linuxmint" => Some(OperatingSystem::Debian),
+            "fedora" | "centos" | "rhel" | "rocky" | "almalinux" => Some(OperatingSystem::RedHat),
+            "arch" | "manjaro" => Some(OperatingSystem::Arch),
+            "alpine" => Some(OperatingSystem::Alpine),
+            "opensuse" | "opensuse-leap" | "opensuse-tumbleweed" | "sles" => {
+                Some(OperatingSystem::Suse)
+            }
+            _ => None,
+        }
     }
 }
 
@@ -82,6 +207,9 @@ pub enum PackageManager {
     Yum,
     Chocolatey,
     Homebrew,
+    Pacman,
+    Apk,
+    Zypper,
 }
 
 impl PackageManager {
@@ -92,6 +220,9 @@ impl PackageManager {
             PackageManager::Yum => "yum install -y",
             PackageManager::Homebrew => "brew install",
             PackageManager::Chocolatey => "choco install",
+            PackageManager::Pacman => "pacman -S --noconfirm",
+            PackageManager::Apk => "apk add",
+            PackageManager::Zypper => "zypper install -y",
         };
 
         format!(
@@ -100,9 +231,76 @@ impl PackageManager {
         )
     }
 
+    /// Program and arguments used to probe whether `package_name` is already installed
+    fn is_installed_probe(&self, package_name: &str) -> (&'static str, Vec<String>) {
+        match self {
+            PackageManager::Apt => ("dpkg", vec!["-s".to_owned(), package_name.to_owned()]),
+            PackageManager::Dnf | PackageManager::Yum | PackageManager::Zypper => {
+                ("rpm", vec!["-q".to_owned(), package_name.to_owned()])
+            }
+            PackageManager::Homebrew => (
+                "brew",
+                vec![
+                    "list".to_owned(),
+                    "--versions".to_owned(),
+                    package_name.to_owned(),
+                ],
+            ),
+            PackageManager::Chocolatey => (
+                "choco",
+                vec![
+                    "list".to_owned(),
+                    "--local-only".to_owned(),
+                    package_name.to_owned(),
+                ],
+            ),
+            PackageManager::Pacman => ("pacman", vec!["-Q".to_owned(), package_name.to_owned()]),
+            PackageManager::Apk => (
+                "apk",
+                vec!["info".to_owned(), "-e".to_owned(), package_name.to_owned()],
+            ),
+        }
+    }
+
+    /// Command used to probe whether `package_name` is already installed
+    pub fn is_installed_command(&self, package_name: &str) -> String {
+        let (program, args) = self.is_installed_probe(package_name);
+        format!("{program} {args}", args = args.join(" "))
+    }
+
+    /// Run the probe from [`PackageManager::is_installed_command`] directly
+    /// (no shell involved, so a package name can't inject extra commands)
+    /// and interpret its result, so callers can skip re-installing a
+    /// dependency that's already present
+    pub async fn check_installed(&self, package_name: &str) -> Result<bool> {
+        let (program, args) = self.is_installed_probe(package_name);
+
+        let output = tokio::process::Command::new(program)
+            .args(&args)
+            .output()
+            .await?;
+
+        let installed = match self {
+            // `choco list` exits zero even when nothing matches, so we also
+            // need to check that the package actually shows up in the output
+            PackageManager::Chocolatey => {
+                output.status.success()
+                    && String::from_utf8_lossy(&output.stdout).contains(package_name)
+            }
+            _ => output.status.success(),
+        };
+
+        Ok(installed)
+    }
+
     pub fn requires_sudo(&self) -> bool {
         match self {
-            PackageManager::Apt | PackageManager::Dnf | PackageManager::Yum => true,
+            PackageManager::Apt
+            | PackageManager::Dnf
+            | PackageManager::Yum
+            | PackageManager::Pacman
+            | PackageManager::Apk
+            | PackageManager::Zypper => true,
             PackageManager::Homebrew | PackageManager::Chocolatey => false,
         }
     }
@@ -110,9 +308,108 @@ impl PackageManager {
     pub fn repology_repository_prefix(&self) -> &[&str] {
         match self {
             PackageManager::Apt => &["debian_", "ubuntu_"],
-            PackageManager::Dnf | PackageManager::Yum => &["fedora_", "centos_"],
+            PackageManager::Dnf | PackageManager::Yum => {
+                &["fedora_", "centos_", "rocky_", "almalinux_"]
+            }
             PackageManager::Chocolatey => &["chocolatey"],
             PackageManager::Homebrew => &["homebrew"],
+            PackageManager::Pacman => &["arch"],
+            PackageManager::Apk => &["alpine_"],
+            PackageManager::Zypper => &["opensuse_"],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn fields(os_release: &str) -> HashMap<String, String> {
+        OperatingSystem::parse_os_release(Cursor::new(os_release))
+    }
+
+    #[test]
+    fn parse_os_release_strips_double_quotes() {
+        let fields = fields("ID=\"debian\"\nVERSION_ID=\"12\"\n");
+
+        assert_eq!(fields.get("ID").map(String::as_str), Some("debian"));
+        assert_eq!(fields.get("VERSION_ID").map(String::as_str), Some("12"));
+    }
+
+    #[test]
+    fn parse_os_release_strips_single_quotes() {
+        let fields = fields("ID='fedora'\n");
+
+        assert_eq!(fields.get("ID").map(String::as_str), Some("fedora"));
+    }
+
+    #[test]
+    fn parse_os_release_ignores_surrounding_whitespace() {
+        let fields = fields("ID = debian   \n");
+
+        assert_eq!(fields.get("ID").map(String::as_str), Some("debian"));
+    }
+
+    #[test]
+    fn parse_os_release_skips_lines_without_an_equals_sign() {
+        let fields = fields("this is a comment, not a field\nID=debian\n");
+
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields.get("ID").map(String::as_str), Some("debian"));
+    }
+
+    #[test]
+    fn os_from_id_maps_known_distro_ids() {
+        assert!(matches!(
+            OperatingSystem::os_from_id("debian"),
+            Some(OperatingSystem::Debian)
+        ));
+        assert!(matches!(
+            OperatingSystem::os_from_id("rocky"),
+            Some(OperatingSystem::RedHat)
+        ));
+        assert!(matches!(
+            OperatingSystem::os_from_id("opensuse-tumbleweed"),
+            Some(OperatingSystem::Suse)
+        ));
+        assert!(OperatingSystem::os_from_id("solaris").is_none());
+    }
+
+    #[test]
+    fn resolve_linux_os_and_version_uses_id_when_recognized() {
+        let fields = fields("ID=\"debian\"\nVERSION_ID=\"12\"\n");
+
+        let (os, version) = OperatingSystem::resolve_linux_os_and_version(&fields).unwrap();
+
+        assert!(matches!(os, OperatingSystem::Debian));
+        assert_eq!(version, Version::from_str("12").unwrap());
+    }
+
+    #[test]
+    fn resolve_linux_os_and_version_falls_back_to_id_like() {
+        let fields = fields("ID=\"pop\"\nID_LIKE=\"ubuntu debian\"\nVERSION_ID=\"22.04\"\n");
+
+        let (os, version) = OperatingSystem::resolve_linux_os_and_version(&fields).unwrap();
+
+        assert!(matches!(os, OperatingSystem::Debian));
+        assert_eq!(version, Version::from_str("22.04").unwrap());
+    }
+
+    #[test]
+    fn resolve_linux_os_and_version_defaults_version_when_missing() {
+        let fields = fields("ID=debian\n");
+
+        let (_, version) = OperatingSystem::resolve_linux_os_and_version(&fields).unwrap();
+
+        assert_eq!(version, Version::default());
+    }
+
+    #[test]
+    fn resolve_linux_os_and_version_returns_none_when_unrecognized() {
+        let fields = fields("ID=solaris\nID_LIKE=solaris\n");
+
+        assert!(OperatingSystem::resolve_linux_os_and_version(&fields).is_none());
+    }
+}
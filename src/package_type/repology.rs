@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::ops::Not;
 
 use reqwest::header::USER_AGENT;
@@ -6,11 +7,13 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::error::{Error, Result};
-use crate::operating_system::OperatingSystem;
+use crate::operating_system::{DetectedOs, OperatingSystem, PackageManager};
+use crate::version::Version;
 
 pub struct RepologyClient {
     client: reqwest::Client,
     base_domain: &'static str,
+    prefer_release: Option<String>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -34,14 +37,141 @@ pub struct Project {
     pub maintainers: Vec<String>,
 }
 
+impl Project {
+    fn is_legacy(&self) -> bool {
+        matches!(self.status.as_str(), "legacy" | "outdated")
+    }
+
+    /// Higher is more desirable: a package confirmed not to be vulnerable
+    /// outranks one with unknown status, which outranks a known-vulnerable one
+    fn vulnerability_rank(&self) -> u8 {
+        match self.vulnerable {
+            Some(false) => 2,
+            None => 1,
+            Some(true) => 0,
+        }
+    }
+}
+
+/// A single best candidate chosen by [`RepologyClient::resolve_best`] for a
+/// given package manager
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPackage {
+    pub binname: Option<String>,
+    pub srcname: Option<String>,
+    pub version: String,
+    pub licenses: Vec<String>,
+    /// `Some(true)`/`Some(false)` mirror Repology's confirmed status;
+    /// `None` means Repology has no vulnerability data for this package
+    pub vulnerable: Option<bool>,
+    pub install_command: String,
+}
+
+impl ResolvedPackage {
+    fn from_project(project: Project, package_manager: &PackageManager) -> Self {
+        let package_name = project
+            .binname
+            .clone()
+            .or_else(|| project.srcname.clone())
+            .unwrap_or_else(|| project.visiblename.clone());
+
+        Self {
+            binname: project.binname,
+            srcname: project.srcname,
+            version: project.version,
+            licenses: project.licenses,
+            vulnerable: project.vulnerable,
+            install_command: package_manager.install(&package_name),
+        }
+    }
+}
+
+/// A single run of either digits or non-digits within a version string
+enum VersionToken {
+    Numeric(u64),
+    Text(String),
+}
+
+/// Split a version string into alternating numeric and non-numeric runs,
+/// e.g. `"10.4a"` -> `[Numeric(10), Text("."), Numeric(4), Text("a")]`
+fn tokenize_version(version: &str) -> Vec<VersionToken> {
+    let mut tokens = Vec::new();
+    let mut chars = version.chars().peekable();
+
+    while let Some(&first) = chars.peek() {
+        let is_digit = first.is_ascii_digit();
+        let mut run = String::new();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_digit() != is_digit {
+                break;
+            }
+
+            run.push(c);
+            chars.next();
+        }
+
+        if is_digit {
+            tokens.push(VersionToken::Numeric(run.parse().unwrap_or(0)));
+        } else {
+            tokens.push(VersionToken::Text(run));
+        }
+    }
+
+    tokens
+}
+
+/// Compare two Repology version strings token-by-token: numeric runs compare
+/// as integers, non-numeric runs compare lexically, and a version that runs
+/// out of tokens first is treated as lower.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a_tokens = tokenize_version(a);
+    let b_tokens = tokenize_version(b);
+
+    for i in 0..a_tokens.len().max(b_tokens.len()) {
+        let ordering = match (a_tokens.get(i), b_tokens.get(i)) {
+            (Some(VersionToken::Numeric(x)), Some(VersionToken::Numeric(y))) => x.cmp(y),
+            (Some(VersionToken::Text(x)), Some(VersionToken::Text(y))) => x.cmp(y),
+            (Some(VersionToken::Numeric(_)), Some(VersionToken::Text(_))) => Ordering::Greater,
+            (Some(VersionToken::Text(_)), Some(VersionToken::Numeric(_))) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
 impl RepologyClient {
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
             base_domain: "https://repology.org/api",
+            prefer_release: None,
         }
     }
 
+    /// Narrow results down to a specific distro release (e.g. `"24.04"`),
+    /// preferring the exact release repo and falling back to the nearest
+    /// older release of the same family, or to its oldest release when every
+    /// one is newer
+    pub fn with_preferred_release(mut self, release: impl Into<String>) -> Self {
+        self.prefer_release = Some(release.into());
+        self
+    }
+
+    /// Like [`RepologyClient::with_preferred_release`], but derived from an
+    /// already-detected OS instead of a raw release string
+    pub fn with_detected_os(self, detected: &DetectedOs) -> Self {
+        let release = format!("{}.{}", detected.version.major, detected.version.minor);
+        self.with_preferred_release(release)
+    }
+
     pub async fn get_projects(
         &self,
         project_name: &str,
@@ -83,6 +213,283 @@ impl RepologyClient {
             })
         });
 
+        if let Some(release) = &self.prefer_release {
+            projects = Self::filter_by_release(projects, package_managers, release);
+        }
+
         Ok(projects)
     }
+
+    /// Within each repo-prefix family (e.g. all `ubuntu_*` repos), keep only
+    /// the exact release repo if present, otherwise the nearest older release
+    /// of that family (or its oldest release if every one is newer than the
+    /// target), so results aren't a mix of unrelated distro versions
+    fn filter_by_release(
+        projects: Vec<Project>,
+        package_managers: &[PackageManager],
+        release: &str,
+    ) -> Vec<Project> {
+        let target = release.parse::<Version>().unwrap_or_default();
+
+        let mut prefixes: Vec<&str> = package_managers
+            .iter()
+            .flat_map(PackageManager::repology_repository_prefix)
+            .copied()
+            .collect();
+        // Some OS families (e.g. RedHat's Dnf/Yum) share the same prefixes
+        prefixes.sort_unstable();
+        prefixes.dedup();
+
+        let mut result = Vec::new();
+
+        for prefix in prefixes {
+            let mut family: Vec<Project> = projects
+                .iter()
+                .filter(|project| project.repo.starts_with(prefix))
+                .cloned()
+                .collect();
+
+            if family.is_empty() {
+                continue;
+            }
+
+            let releases: Vec<(String, Version)> = family
+                .iter()
+                .filter_map(|project| {
+                    let version = Self::release_suffix(&project.repo, prefix)?
+                        .replace('_', ".")
+                        .parse::<Version>()
+                        .ok()?;
+
+                    Some((project.repo.clone(), version))
+                })
+                .collect();
+
+            // Prefixes like "homebrew" or "chocolatey" don't encode a release
+            if releases.is_empty() {
+                result.append(&mut family);
+                continue;
+            }
+
+            // Prefer the exact release, then the nearest older one, then
+            // (if every known release is newer than the target) the oldest
+            // one available rather than mixing every release together
+            let chosen_repo = releases
+                .iter()
+                .find(|(_, version)| *version == target)
+                .or_else(|| {
+                    releases
+                        .iter()
+                        .filter(|(_, version)| *version <= target)
+                        .max_by_key(|(_, version)| *version)
+                })
+                .or_else(|| releases.iter().min_by_key(|(_, version)| *version))
+                .map(|(repo, _)| repo.clone())
+                .expect("releases is non-empty");
+
+            family.retain(|project| project.repo == chosen_repo);
+            result.append(&mut family);
+        }
+
+        result
+    }
+
+    /// The trailing release portion of a repo name, e.g. `"24_04"` from
+    /// `"ubuntu_24_04"` given the prefix `"ubuntu_"`, if it looks numeric.
+    /// Some families put a non-numeric sub-repo name between the prefix and
+    /// the release (`opensuse_leap_15_5`, `centos_stream_9`) — that infix is
+    /// stripped before the digit check.
+    fn release_suffix<'a>(repo: &'a str, prefix: &str) -> Option<&'a str> {
+        const RELEASE_INFIXES: &[&str] = &["leap_", "tumbleweed_", "stream_"];
+
+        let suffix = repo.strip_prefix(prefix)?;
+        let suffix = RELEASE_INFIXES
+            .iter()
+            .find_map(|infix| suffix.strip_prefix(infix))
+            .unwrap_or(suffix);
+
+        suffix
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit())
+            .then_some(suffix)
+    }
+
+    /// Resolve the single best installable candidate per package manager
+    /// for `project_name`, favouring non-legacy statuses, packages known
+    /// not to be vulnerable, and the highest version.
+    pub async fn resolve_best(
+        &self,
+        project_name: &str,
+        os: OperatingSystem,
+    ) -> Result<Vec<ResolvedPackage>> {
+        let projects = self.get_projects(project_name, os).await?;
+        let package_managers = os.package_managers();
+
+        let mut candidates_by_manager: Vec<Vec<Project>> =
+            package_managers.iter().map(|_| Vec::new()).collect();
+
+        for project in projects {
+            let matched = package_managers.iter().position(|package_manager| {
+                package_manager
+                    .repology_repository_prefix()
+                    .iter()
+                    .any(|prefix| project.repo.starts_with(prefix))
+            });
+
+            if let Some(index) = matched {
+                candidates_by_manager[index].push(project);
+            }
+        }
+
+        let resolved = candidates_by_manager
+            .into_iter()
+            .zip(package_managers)
+            .filter_map(|(candidates, package_manager)| {
+                Self::pick_best(candidates)
+                    .map(|project| ResolvedPackage::from_project(project, package_manager))
+            })
+            .collect();
+
+        Ok(resolved)
+    }
+
+    /// Narrow candidates down to the single best one: drop legacy/outdated
+    /// entries when a current one exists, then prefer known-safe packages,
+    /// then the highest version.
+    fn pick_best(candidates: Vec<Project>) -> Option<Project> {
+        let has_current = candidates.iter().any(|project| !project.is_legacy());
+
+        candidates
+            .into_iter()
+            .filter(|project| !has_current || !project.is_legacy())
+            .max_by(|a, b| {
+                a.vulnerability_rank()
+                    .cmp(&b.vulnerability_rank())
+                    .then_with(|| compare_versions(&a.version, &b.version))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(repo: &str, version: &str) -> Project {
+        Project {
+            repo: repo.to_owned(),
+            version: version.to_owned(),
+            status: "newest".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filter_by_release_prefers_exact_release() {
+        let projects = vec![
+            project("ubuntu_22_04", "1.0"),
+            project("ubuntu_24_04", "2.0"),
+        ];
+
+        let resolved = RepologyClient::filter_by_release(
+            projects,
+            OperatingSystem::Debian.package_managers(),
+            "24.04",
+        );
+
+        assert_eq!(
+            resolved.iter().map(|p| p.repo.as_str()).collect::<Vec<_>>(),
+            vec!["ubuntu_24_04"]
+        );
+    }
+
+    #[test]
+    fn filter_by_release_falls_back_to_nearest_older_release() {
+        let projects = vec![
+            project("ubuntu_18_04", "1.0"),
+            project("ubuntu_20_04", "2.0"),
+            project("ubuntu_24_04", "3.0"),
+        ];
+
+        let resolved = RepologyClient::filter_by_release(
+            projects,
+            OperatingSystem::Debian.package_managers(),
+            "22.04",
+        );
+
+        assert_eq!(
+            resolved.iter().map(|p| p.repo.as_str()).collect::<Vec<_>>(),
+            vec!["ubuntu_20_04"]
+        );
+    }
+
+    #[test]
+    fn filter_by_release_falls_back_to_oldest_when_only_newer_releases_exist() {
+        let projects = vec![
+            project("ubuntu_22_04", "1.0"),
+            project("ubuntu_24_04", "2.0"),
+        ];
+
+        let resolved = RepologyClient::filter_by_release(
+            projects,
+            OperatingSystem::Debian.package_managers(),
+            "18.04",
+        );
+
+        assert_eq!(
+            resolved.iter().map(|p| p.repo.as_str()).collect::<Vec<_>>(),
+            vec!["ubuntu_22_04"]
+        );
+    }
+
+    #[test]
+    fn filter_by_release_leaves_releaseless_prefixes_untouched() {
+        let projects = vec![project("homebrew", "1.0")];
+
+        let resolved = RepologyClient::filter_by_release(
+            projects,
+            OperatingSystem::Mac.package_managers(),
+            "14.5",
+        );
+
+        assert_eq!(resolved.len(), 1);
+    }
+
+    #[test]
+    fn filter_by_release_recognizes_release_infixes() {
+        let projects = vec![
+            project("opensuse_leap_15_5", "1.2"),
+            project("opensuse_leap_15_6", "1.3"),
+            project("opensuse_tumbleweed_20240101", "1.4"),
+        ];
+
+        let resolved = RepologyClient::filter_by_release(
+            projects,
+            OperatingSystem::Suse.package_managers(),
+            "15.5",
+        );
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].repo, "opensuse_leap_15_5");
+    }
+
+    #[test]
+    fn compare_versions_numeric_runs_compare_as_integers() {
+        assert_eq!(compare_versions("10", "9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn compare_versions_text_runs_compare_lexically() {
+        assert_eq!(compare_versions("1.2a", "1.2b"), Ordering::Less);
+    }
+
+    #[test]
+    fn compare_versions_equal_versions_are_equal() {
+        assert_eq!(compare_versions("1.2.3", "1.2.3"), Ordering::Equal);
+    }
+
+    #[test]
+    fn compare_versions_missing_trailing_component_is_lower() {
+        assert_eq!(compare_versions("1.2", "1.2.1"), Ordering::Less);
+    }
 }
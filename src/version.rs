@@ -0,0 +1,113 @@
+use std::{convert::Infallible, result::Result as StdResult, str::FromStr};
+
+/// A normalized `major.minor.patch` version, parsed leniently from
+/// whatever format a distro or OS vendor happens to report
+/// (e.g. `"24.04"`, `"14.5"`, `"10.0.19045"`), with missing components
+/// defaulting to `0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl FromStr for Version {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        let mut components = s
+            .trim()
+            .split(|c: char| c == '.' || c == '-' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(Self::leading_digits);
+
+        Ok(Version {
+            major: components.next().unwrap_or(0),
+            minor: components.next().unwrap_or(0),
+            patch: components.next().unwrap_or(0),
+        })
+    }
+}
+
+impl Version {
+    /// Parse the leading run of ASCII digits in a version component,
+    /// defaulting to `0` if there isn't one (e.g. a `-beta` suffix)
+    fn leading_digits(component: &str) -> u64 {
+        component
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_two_component_version() {
+        assert_eq!(
+            "24.04".parse(),
+            Ok(Version {
+                major: 24,
+                minor: 4,
+                patch: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_another_two_component_version() {
+        assert_eq!(
+            "14.5".parse(),
+            Ok(Version {
+                major: 14,
+                minor: 5,
+                patch: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_windows_build_string() {
+        assert_eq!(
+            "10.0.19045".parse(),
+            Ok(Version {
+                major: 10,
+                minor: 0,
+                patch: 19045,
+            })
+        );
+    }
+
+    #[test]
+    fn defaults_missing_components_to_zero() {
+        assert_eq!(
+            "7".parse(),
+            Ok(Version {
+                major: 7,
+                minor: 0,
+                patch: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn hyphenated_non_numeric_suffix_parses_as_a_zero_component() {
+        assert_eq!(
+            "1.2-beta".parse(),
+            Ok(Version {
+                major: 1,
+                minor: 2,
+                patch: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn empty_string_defaults_to_all_zeroes() {
+        assert_eq!("".parse(), Ok(Version::default()));
+    }
+}